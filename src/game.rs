@@ -1,9 +1,17 @@
-use crossterm::event::{self, Event, KeyCode};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::style::{Color, Print, ResetColor, SetForegroundColor};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
 
 use rand::{seq::SliceRandom, thread_rng};
 use std::collections::VecDeque;
+use std::fs;
 use std::io::{stdout, Write};
-use std::time::{Duration, Instant};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
 
 
 #[derive(Copy, Clone, PartialOrd, PartialEq)]
@@ -36,7 +44,11 @@ pub struct Snake {
 impl Snake {
     pub fn new(board_size: usize) -> Snake {
         Snake {
-            direction: Direction::LEFT,
+            // UP is the only direction that doesn't immediately collide with
+            // the body or the board edge from this starting layout on a
+            // non-wrapping board (with wrap, DOWN and LEFT also work): the tick
+            // loop calls `step()` before the player gets a chance to turn.
+            direction: Direction::UP,
             body: {
                 let mut res = VecDeque::with_capacity(board_size * board_size + 3);
                 res.push_back(Coordinate { x: 0, y: 0 });
@@ -57,60 +69,197 @@ impl Snake {
 }
 
 
+/// Reads `crossterm` key events on a dedicated background thread and
+/// forwards them over a channel, so the main loop never blocks on input.
+pub struct Events {
+    rx: Receiver<KeyEvent>,
+}
+
+impl Events {
+    fn spawn() -> Events {
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || loop {
+            if let Ok(true) = event::poll(Duration::from_millis(10)) {
+                if let Ok(Event::Key(key_event)) = event::read() {
+                    if tx.send(key_event).is_err() {
+                        break; // receiver dropped, game has ended
+                    }
+                }
+            }
+        });
+
+        Events { rx }
+    }
+
+    /// Pops the oldest pending key event, if any.
+    pub fn next(&self) -> Option<KeyEvent> {
+        self.rx.try_recv().ok()
+    }
+}
+
+
+/// Puts the terminal into raw mode on the alternate screen with the
+/// cursor hidden, and restores the original terminal state when dropped.
+/// Keep this alive for as long as the game runs so `Drop` always fires on
+/// the way out, including on the `Ctrl-C` path.
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    pub fn new() -> std::io::Result<TerminalGuard> {
+        enable_raw_mode()?;
+        execute!(stdout(), EnterAlternateScreen, Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(stdout(), Show, LeaveAlternateScreen);
+        let _ = disable_raw_mode();
+    }
+}
+
+
+fn highscore_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".rust_snake_highscore")
+}
+
+fn read_highscore() -> usize {
+    fs::read_to_string(highscore_path())
+        .ok()
+        .and_then(|contents| contents.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+fn write_highscore(score: usize) {
+    let _ = fs::write(highscore_path(), score.to_string());
+}
+
+
+/// How many pre-queued turns `Game::queue_direction` will hold onto before
+/// it starts dropping the oldest ones, i.e. how far ahead a player can
+/// buffer corner turns.
+const MOVE_BUFFER_CAPACITY: usize = 5;
+
+
+/// Builds a cyclic ordering of every `(x, y)` cell on a `board_size *
+/// board_size` board such that consecutive cells (including the last
+/// wrapping to the first) are grid-adjacent. Used by the autopilot to
+/// follow a path that is guaranteed to visit every cell without ever
+/// trapping itself. Only valid for an even `board_size`.
+fn build_hamiltonian_cycle(board_size: usize) -> (Vec<Coordinate>, Vec<usize>) {
+    assert!(board_size % 2 == 0, "Hamiltonian autopilot requires an even board size");
+
+    let mut cycle = Vec::with_capacity(board_size * board_size);
+
+    // Column 0: straight up.
+    for y in 0..board_size {
+        cycle.push(Coordinate { x: 0, y: y as u8 });
+    }
+
+    // Remaining columns: zig-zag through rows 1..board_size, leaving row 0
+    // free as the return corridor.
+    for x in 1..board_size {
+        if x % 2 == 1 {
+            for y in (1..board_size).rev() {
+                cycle.push(Coordinate { x: x as u8, y: y as u8 });
+            }
+        } else {
+            for y in 1..board_size {
+                cycle.push(Coordinate { x: x as u8, y: y as u8 });
+            }
+        }
+    }
+
+    // Row 0: walk back from the last column to column 0, closing the cycle.
+    for x in (1..board_size).rev() {
+        cycle.push(Coordinate { x: x as u8, y: 0 });
+    }
+
+    let mut index = vec![0usize; board_size * board_size];
+    for (i, coord) in cycle.iter().enumerate() {
+        index[coord.x as usize * board_size + coord.y as usize] = i;
+    }
+
+    (cycle, index)
+}
+
+
+/// How many consecutive autopilot ticks are allowed to pass without beating
+/// `shortcut_best_distance` before shortcuts are disabled and the autopilot
+/// falls back to plain cycle-following, which always makes guaranteed
+/// progress toward the food.
+const SHORTCUT_STALL_LIMIT: usize = 8;
+
+/// Given the best head-to-food distance seen since the food last moved and
+/// the latest measurement, returns the updated `(best_distance, stale_ticks)`
+/// pair: a new best resets the stall counter, otherwise it increments.
+fn track_shortcut_progress(best_so_far: i64, latest_distance: i64, stale_ticks: usize) -> (i64, usize) {
+    if latest_distance < best_so_far {
+        (latest_distance, 0)
+    } else {
+        (best_so_far, stale_ticks + 1)
+    }
+}
+
+
 pub struct Game {
     board_size: usize,
     snake: Snake,
     food_pos: Coordinate,
     pub freeze_time_ms: u64,
+    pub events: Events,
+    move_buffer: VecDeque<Direction>,
+    wrap: bool,
+    hamiltonian_cycle: Vec<Coordinate>,
+    hamiltonian_index: Vec<usize>,
+    score: usize,
+    best_score: usize,
+    shortcut_anchor_food: Option<Coordinate>,
+    shortcut_best_distance: i64,
+    shortcut_stale_ticks: usize,
 }
 
 
 impl Game {
-    pub fn clear_terminal() {
-        print!("\x1B[2J\x1B[1;1H");
-    }
-
-    pub fn get_user_key(&self) -> Result<Direction, &'static str> {
-        print!("Please enter your move (w/a/s/d): ");
-        stdout().flush().unwrap();
-
-        let mut last_input_time = Instant::now();
-        let debounce_duration = Duration::from_millis(self.freeze_time_ms);
-
-        loop {
-            if event::poll(Duration::from_millis(10)).unwrap() {
-                if let Event::Key(key_event) = event::read().unwrap() {
-                    let current_time = Instant::now();
-                    if current_time.duration_since(last_input_time) < debounce_duration {
-                        continue;
-                    }
-
-                    last_input_time = current_time;
-
-                    let result = match key_event.code {
-                        KeyCode::Char('w') => Ok(Direction::UP),
-                        KeyCode::Char('a') => Ok(Direction::LEFT),
-                        KeyCode::Char('s') => Ok(Direction::DOWN),
-                        KeyCode::Char('d') => Ok(Direction::RIGHT),
-                        KeyCode::Char('c') if key_event.modifiers.contains(event::KeyModifiers::CONTROL) => {
-                            Err("User interrupted")
-                        }
-                        _ => continue,
-                    };
-
-                    Self::clear_terminal();
-                    return result;
-                }
+    fn decode_key(key_event: KeyEvent) -> Result<Direction, &'static str> {
+        match key_event.code {
+            KeyCode::Char('w') => Ok(Direction::UP),
+            KeyCode::Char('a') => Ok(Direction::LEFT),
+            KeyCode::Char('s') => Ok(Direction::DOWN),
+            KeyCode::Char('d') => Ok(Direction::RIGHT),
+            KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                Err("User interrupted")
             }
+            _ => Err("Unrecognized key"),
         }
     }
 
-    pub fn new(board_size: usize) -> Game {
+    /// Pops the oldest pending key press, if any, decoded into a direction.
+    pub fn next_key(&self) -> Option<Result<Direction, &'static str>> {
+        self.events.next().map(Self::decode_key)
+    }
+
+    pub fn new(board_size: usize, wrap: bool) -> Game {
+        let (hamiltonian_cycle, hamiltonian_index) = build_hamiltonian_cycle(board_size);
+
         Game {
             board_size,
             snake: Snake::new(board_size),
             food_pos: Coordinate { x: 0, y: 1 },
             freeze_time_ms: 200,
+            events: Events::spawn(),
+            move_buffer: VecDeque::with_capacity(MOVE_BUFFER_CAPACITY),
+            wrap,
+            hamiltonian_cycle,
+            hamiltonian_index,
+            score: 0,
+            best_score: read_highscore(),
+            shortcut_anchor_food: None,
+            shortcut_best_distance: 0,
+            shortcut_stale_ticks: 0,
         }
     }
 
@@ -130,26 +279,140 @@ impl Game {
     }
 
 
-    pub fn change_snake_dir(&mut self, new_dir: Direction) {
-        self.snake.direction = new_dir;
+    /// Queues `new_dir` as the next turn to apply, validated against the
+    /// most recently buffered direction (or the snake's current direction
+    /// if nothing is buffered yet) so two quick keypresses can't be
+    /// reversed against each other. Drops the oldest buffered turn once
+    /// `MOVE_BUFFER_CAPACITY` is reached.
+    pub fn queue_direction(&mut self, new_dir: Direction) -> Result<(), &'static str> {
+        let reference_dir = self.move_buffer.back().copied().unwrap_or(self.snake.direction);
+
+        if new_dir.get_opposite() == reference_dir {
+            return Err("Opposite Direction!Opposite Direction!Opposite Direction!");
+        }
+
+        if self.move_buffer.len() >= MOVE_BUFFER_CAPACITY {
+            self.move_buffer.pop_front();
+        }
+        self.move_buffer.push_back(new_dir);
+
+        Ok(())
+    }
+
+    fn direction_between(from: Coordinate, to: Coordinate) -> Option<Direction> {
+        match (to.x as i64 - from.x as i64, to.y as i64 - from.y as i64) {
+            (0, 1) => Some(Direction::UP),
+            (0, -1) => Some(Direction::DOWN),
+            (-1, 0) => Some(Direction::LEFT),
+            (1, 0) => Some(Direction::RIGHT),
+            _ => None,
+        }
+    }
+
+    fn manhattan_distance(a: Coordinate, b: Coordinate) -> i64 {
+        (a.x as i64 - b.x as i64).abs() + (a.y as i64 - b.y as i64).abs()
+    }
+
+    fn hamiltonian_index_of(&self, coord: Coordinate) -> usize {
+        self.hamiltonian_index[coord.x as usize * self.board_size + coord.y as usize]
+    }
+
+    /// Picks the next move for the autopilot. Normally just follows the
+    /// precomputed Hamiltonian cycle (which visits every cell and so can
+    /// never trap the snake), but takes a shortcut toward the food when
+    /// one is safely available, i.e. it stays strictly ahead of the tail
+    /// in cycle order. Shortcuts are disabled once the snake fills more
+    /// than half the board, where the safety margin gets too thin, and
+    /// also once `SHORTCUT_STALL_LIMIT` consecutive ticks have passed
+    /// without the snake beating its closest distance to the food since
+    /// the food last moved — a greedy one-step Manhattan improvement isn't
+    /// guaranteed to converge, so without this guard the snake can
+    /// oscillate between a few cells forever instead of falling back to
+    /// plain cycle-following, which always makes guaranteed progress.
+    pub fn ai_next_direction(&mut self) -> Direction {
+        let head = *self.snake.get_head();
+        let tail = *self.snake.body.back().unwrap(); // tail always exists as length > 0
+        let cycle_len = self.hamiltonian_cycle.len();
+
+        let head_to_food = Self::manhattan_distance(head, self.food_pos);
+
+        if self.shortcut_anchor_food != Some(self.food_pos) {
+            self.shortcut_anchor_food = Some(self.food_pos);
+            self.shortcut_best_distance = head_to_food;
+            self.shortcut_stale_ticks = 0;
+        }
+
+        let head_idx = self.hamiltonian_index_of(head);
+        let mut best = self.hamiltonian_cycle[(head_idx + 1) % cycle_len];
+
+        let shortcuts_enabled = self.snake.body.len() * 2 <= self.board_size * self.board_size
+            && self.shortcut_stale_ticks < SHORTCUT_STALL_LIMIT;
+
+        if shortcuts_enabled {
+            let tail_idx = self.hamiltonian_index_of(tail);
+            let dist_to_tail = (tail_idx + cycle_len - head_idx) % cycle_len;
+            let mut best_dist = (self.hamiltonian_index_of(best) + cycle_len - head_idx) % cycle_len;
+
+            let neighbors = [
+                Coordinate { x: head.x, y: head.y.wrapping_add(1) },
+                Coordinate { x: head.x, y: head.y.wrapping_sub(1) },
+                Coordinate { x: head.x.wrapping_sub(1), y: head.y },
+                Coordinate { x: head.x.wrapping_add(1), y: head.y },
+            ];
+
+            for &candidate in neighbors.iter() {
+                if candidate.x as usize >= self.board_size || candidate.y as usize >= self.board_size {
+                    continue;
+                }
+                if self.snake.body.contains(&candidate) {
+                    continue;
+                }
+
+                let cand_dist = (self.hamiltonian_index_of(candidate) + cycle_len - head_idx) % cycle_len;
+                let is_safe_shortcut = cand_dist > 0 && cand_dist < dist_to_tail;
+                let moves_toward_food = Self::manhattan_distance(candidate, self.food_pos) < head_to_food;
+
+                if is_safe_shortcut && moves_toward_food && cand_dist > best_dist {
+                    best = candidate;
+                    best_dist = cand_dist;
+                }
+            }
+        }
+
+        let (best_distance, stale_ticks) =
+            track_shortcut_progress(self.shortcut_best_distance, head_to_food, self.shortcut_stale_ticks);
+        self.shortcut_best_distance = best_distance;
+        self.shortcut_stale_ticks = stale_ticks;
+
+        Self::direction_between(head, best).unwrap_or(self.snake.direction)
     }
 
     pub fn step(&mut self) -> bool {
+        if let Some(next_dir) = self.move_buffer.pop_front() {
+            self.snake.direction = next_dir;
+        }
+
         let Coordinate { x: x_head, y: y_head } = *self.snake.get_head();
 
         let next_coord = match self.snake.direction {
             Direction::UP if y_head as usize + 1 < self.board_size => Coordinate { x: x_head, y: y_head + 1 },
+            Direction::UP if self.wrap => Coordinate { x: x_head, y: 0 },
             Direction::DOWN if y_head as i64 - 1 >= 0 => Coordinate { x: x_head, y: y_head - 1 },
+            Direction::DOWN if self.wrap => Coordinate { x: x_head, y: (self.board_size - 1) as u8 },
             Direction::LEFT if x_head as i64 - 1 >= 0 => Coordinate { x: x_head - 1, y: y_head },
+            Direction::LEFT if self.wrap => Coordinate { x: (self.board_size - 1) as u8, y: y_head },
             Direction::RIGHT if x_head as usize + 1 < self.board_size => Coordinate { x: x_head + 1, y: y_head },
+            Direction::RIGHT if self.wrap => Coordinate { x: 0, y: y_head },
             _ => {
-                println!("GAME OVER (out of bounce)");
+                print!("GAME OVER (out of bounce)\r\n");
+                self.print_final_summary();
                 return false;
             }
         };
 
         if self.snake.body.contains(&next_coord) {
-            println!("GAME OVER (collision with the snake)");
+            print!("GAME OVER (collision with the snake)\r\n");
+            self.print_final_summary();
             return false;
         }
 
@@ -157,8 +420,11 @@ impl Game {
 
         match eat_food {
             true => {
+                self.record_food_eaten();
+
                 if self.snake.body.len() + 1 >= self.board_size * self.board_size {
-                    println!("WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!");
+                    print!("WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!WIN!\r\n");
+                    self.print_final_summary();
                     return false;
                 } else {
                     self.snake.body.push_front(self.food_pos);
@@ -175,8 +441,27 @@ impl Game {
         true
     }
 
-    pub fn get_current_dir(&self) -> Direction {
-        self.snake.direction
+    fn record_food_eaten(&mut self) {
+        self.score += 1;
+
+        if self.score > self.best_score {
+            self.best_score = self.score;
+            write_highscore(self.best_score);
+        }
+    }
+
+    fn print_final_summary(&self) {
+        print!("Final Score: {}  Best: {}\r\n", self.score, self.best_score);
+        stdout().flush().unwrap();
+    }
+
+    fn cell_color(cell: char) -> Color {
+        match cell {
+            'H' => Color::Yellow,
+            'S' => Color::Green,
+            'F' => Color::Red,
+            _ => Color::DarkGrey,
+        }
     }
 
     pub fn display_state(&self) {
@@ -191,11 +476,83 @@ impl Game {
 
         board[self.board_size - 1 - self.food_pos.y as usize][self.food_pos.x as usize] = 'F';
 
+        let mut out = stdout();
+
+        queue!(out, MoveTo(0, 0), Clear(ClearType::All)).unwrap();
+        queue!(out, Print(format!("Score: {}  Best: {}\r\n", self.score, self.best_score))).unwrap();
+
         for row in board.iter() {
-            for cell in row.iter() {
-                print!("{} ", cell);
+            for &cell in row.iter() {
+                queue!(out, SetForegroundColor(Self::cell_color(cell)), Print(cell), Print(' '), ResetColor).unwrap();
+            }
+            queue!(out, Print("\r\n")).unwrap();
+        }
+
+        out.flush().unwrap();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_adjacent(a: Coordinate, b: Coordinate) -> bool {
+        let dx = (a.x as i64 - b.x as i64).abs();
+        let dy = (a.y as i64 - b.y as i64).abs();
+        (dx, dy) == (1, 0) || (dx, dy) == (0, 1)
+    }
+
+    #[test]
+    fn hamiltonian_cycle_visits_every_cell_exactly_once() {
+        for board_size in [4, 6, 8] {
+            let (cycle, index) = build_hamiltonian_cycle(board_size);
+
+            assert_eq!(cycle.len(), board_size * board_size);
+
+            let mut seen = vec![false; board_size * board_size];
+            for coord in &cycle {
+                let flat = coord.x as usize * board_size + coord.y as usize;
+                assert!(!seen[flat], "cell ({}, {}) visited twice", coord.x, coord.y);
+                seen[flat] = true;
+            }
+            assert!(seen.iter().all(|&visited| visited), "not every cell was visited");
+
+            for (i, coord) in cycle.iter().enumerate() {
+                let flat = coord.x as usize * board_size + coord.y as usize;
+                assert_eq!(index[flat], i, "index lookup disagrees with cycle position");
+            }
+        }
+    }
+
+    #[test]
+    fn hamiltonian_cycle_consecutive_cells_are_adjacent() {
+        for board_size in [4, 6, 8] {
+            let (cycle, _) = build_hamiltonian_cycle(board_size);
+
+            for window in cycle.windows(2) {
+                assert!(is_adjacent(window[0], window[1]), "non-adjacent step in cycle");
             }
-            println!();
+
+            // The cycle wraps: the last cell must be adjacent to the first.
+            assert!(is_adjacent(*cycle.last().unwrap(), cycle[0]), "cycle doesn't close");
         }
     }
+
+    #[test]
+    #[should_panic(expected = "even board size")]
+    fn hamiltonian_cycle_rejects_odd_board_size() {
+        build_hamiltonian_cycle(5);
+    }
+
+    #[test]
+    fn shortcut_progress_resets_stale_ticks_on_new_best() {
+        assert_eq!(track_shortcut_progress(10, 7, 3), (7, 0));
+    }
+
+    #[test]
+    fn shortcut_progress_accumulates_stale_ticks_without_improvement() {
+        assert_eq!(track_shortcut_progress(5, 5, 3), (5, 4));
+        assert_eq!(track_shortcut_progress(5, 6, 3), (5, 4));
+    }
 }
\ No newline at end of file
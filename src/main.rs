@@ -1,32 +1,86 @@
 mod game;
 
 use game::*;
-use std::process;
+use std::io::{stdin, stdout, Write};
+use std::thread;
+use std::time::{Duration, Instant};
 
 
+const BOARD_SIZE: usize = 6;
+
+
+fn prompt_yes_no(question: &str) -> bool {
+    println!("{question} (y/n): ");
+
+    let mut answer = String::new();
+    stdin().read_line(&mut answer).unwrap();
+
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Blocks until a key is pressed, so a GAME OVER/WIN summary stays on
+/// screen instead of being wiped by the next redraw or by the terminal
+/// guard restoring the primary screen on exit.
+fn wait_for_keypress(game: &Game) {
+    loop {
+        if game.next_key().is_some() {
+            return;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
 fn main() {
-    let mut game = Game::new(5);
-    
+    let wrap = prompt_yes_no("Wrap-around board?");
+    let autopilot = prompt_yes_no("Autopilot (AI) mode?");
+
+    let _terminal_guard = TerminalGuard::new().unwrap();
+
+    let mut game = Game::new(BOARD_SIZE, wrap);
+
     let mut is_game_running = true;
+    let mut game_over = false;
+
+    game.display_state();
 
     while is_game_running {
-        game.display_state();
+        while let Some(key_result) = game.next_key() {
+            match key_result {
+                Ok(new_dir) => {
+                    if let Err(e) = game.queue_direction(new_dir) {
+                        print!("{e}\r\n");
+                    }
+                }
+                Err("User interrupted") => is_game_running = false,
+                Err(_) => {}
+            }
+        }
 
-        let key_pressed = game.get_user_key();
+        if !is_game_running {
+            break;
+        }
 
-        match key_pressed {
-            Ok(new_dir) if new_dir.get_opposite() == game.get_current_dir() => {
-                println!("Opposite Direction!Opposite Direction!Opposite Direction!");
-                continue;
-            }
-            Ok(key_pressed) => game.change_snake_dir(key_pressed),
-            Err(e) if e == "User interrupted" => process::exit(0),
-            Err(e) => {
-                println!("{e}");
-                continue;
-            }
-        };
+        if autopilot {
+            let ai_dir = game.ai_next_direction();
+            let _ = game.queue_direction(ai_dir);
+        }
+
+        let tick_deadline = Instant::now() + Duration::from_millis(game.freeze_time_ms);
+        while Instant::now() < tick_deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
 
         is_game_running = game.step();
+        if is_game_running {
+            game.display_state();
+        } else {
+            game_over = true;
+        }
+    }
+
+    if game_over {
+        print!("\r\nPress any key to continue...\r\n");
+        stdout().flush().unwrap();
+        wait_for_keypress(&game);
     }
 }